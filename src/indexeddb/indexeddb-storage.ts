@@ -31,6 +31,32 @@ export class IndexedDBStorage implements TGStorage
         }).then(() => req.result);
     }
 
+    getMany(keys: IDBValidKey[]): Promise<(TGNode|null)[]>
+    {
+        const reqs: IDBRequest[] = [];
+        // Intentionally readonly: a batch read never writes, so it doesn't need to lock the store
+        return this._withIDBStore('readonly', (store) =>
+        {
+            for (const key of keys)
+            {
+                reqs.push(store.get(key));
+            }
+        }).then(() => reqs.map(req => req.result || null));
+    }
+
+    containsKeys(keys: IDBValidKey[]): Promise<boolean[]>
+    {
+        const reqs: IDBRequest<number>[] = [];
+        // Counts keys without loading the stored nodes
+        return this._withIDBStore('readonly', (store) =>
+        {
+            for (const key of keys)
+            {
+                reqs.push(store.count(key));
+            }
+        }).then(() => reqs.map(req => req.result > 0));
+    }
+
     async list(options: StorageListOptions): Promise<TGGraphData>
     {
         const allNodes = await this.getAll();
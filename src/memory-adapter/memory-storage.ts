@@ -38,6 +38,16 @@ export class MemoryStorage implements TGStorage
         return Promise.resolve(this.getSync(key));
     }
 
+    getMany(keys: string[]): Promise<(TGNode|null)[]>
+    {
+        return Promise.resolve(keys.map(key => this.getSync(key)));
+    }
+
+    containsKeys(keys: string[]): Promise<boolean[]>
+    {
+        return Promise.resolve(keys.map(key => this.map.has(key)));
+    }
+
     putSync(key: string, value: TGNode): void
     {
         this.map.set(key, value);
@@ -1,2 +1,5 @@
 export * from './server';
 export * from './server-options';
+export { createGraphAdapter, EncryptedStorage } from '../storage';
+export type { EncryptedStorageOptions, TGStorage, StorageListOptions } from '../storage';
+export { MemoryStorage } from '../memory-adapter';
\ No newline at end of file
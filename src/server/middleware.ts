@@ -1,10 +1,27 @@
+import { isNumber } from 'topgun-typed';
 import { TGSocketServer, TGSocket, RequestObject } from 'topgun-socket/server';
 import { TGServerOptions } from './server-options';
 import { TGGraphAdapter, TGGraphData, TGMessage, TGOptionsGet } from '../types';
 import { pseudoRandomText } from '../sea';
 
+interface SocketQueue
+{
+    tail: Promise<void>;
+    /** Number of puts and reads queued or in-flight */
+    size: number;
+}
+
+function nodeChannelSoul(channel: string): string|null
+{
+    const soul = channel.replace(/^topgun\/nodes\//, '');
+
+    return !soul || soul === channel || soul === 'changelog' ? null : soul;
+}
+
 export class Middleware
 {
+    private readonly socketQueues = new WeakMap<TGSocket, SocketQueue>();
+
     /**
      * Constructor
      */
@@ -46,13 +63,72 @@ export class Middleware
             return;
         }
 
-        this.processPut(msg).then((data) =>
+        this.enqueue(
+            req.socket,
+            () => this.processPut(msg).then((data) =>
+            {
+                req.socket.transmit('#publish', {
+                    channel: `topgun/@${msg['#']}`,
+                    data
+                });
+            }),
+            () =>
+            {
+                req.socket.transmit('#publish', {
+                    channel: `topgun/@${msg['#']}`,
+                    data   : {
+                        '#'  : pseudoRandomText(),
+                        '@'  : msg['#'],
+                        'err': 'Too many pending messages',
+                        'ok' : false,
+                    }
+                });
+            },
+        );
+    }
+
+    /**
+     * Puts and reads from one socket are handled in the order they were sent,
+     * messages from different sockets are still processed concurrently
+     */
+    private enqueue(socket: TGSocket, handle: () => Promise<void>, reject: () => void): void
+    {
+        const queue       = this.getSocketQueue(socket);
+        const maxMessages = this.options.maxQueuedMessages;
+        const warn        = (e: any) =>
         {
-            req.socket.transmit('#publish', {
-                channel: `topgun/@${msg['#']}`,
-                data
+            // tslint:disable-next-line: no-console
+            console.warn(e.stack || e);
+        };
+
+        if (isNumber(maxMessages) && queue.size >= maxMessages)
+        {
+            // Rejections go through the queue too, so replies keep the send order
+            queue.tail = queue.tail.then(reject).catch(warn);
+            return;
+        }
+
+        queue.size++;
+        queue.tail = queue.tail
+            .then(handle)
+            .catch(warn)
+            .then(() =>
+            {
+                queue.size--;
             });
-        })
+    }
+
+    private getSocketQueue(socket: TGSocket): SocketQueue
+    {
+        let queue = this.socketQueues.get(socket);
+
+        if (!queue)
+        {
+            queue = { tail: Promise.resolve(), size: 0 };
+            this.socketQueues.set(socket, queue);
+        }
+
+        return queue;
     }
 
     private async subscribeMiddleware(req: RequestObject): Promise<void>
@@ -62,43 +138,81 @@ export class Middleware
             return;
         }
 
-        const soul = req.channel.replace(/^topgun\/nodes\//, '');
+        const soul = nodeChannelSoul(req.channel);
 
-        if (!soul || soul === req.channel || soul === 'changelog')
+        if (!soul)
         {
             return;
         }
 
+        this.assertSubscriptionLimit(req.socket, req.channel);
+
         const opts  = req.data as TGOptionsGet|undefined;
         const msgId = Math.random()
             .toString(36)
             .slice(2);
-
-        this.readNodes(opts)
-            .then(graphData => ({
-                channel: req.channel,
-                data   : {
-                    '#'  : msgId,
-                    'put': graphData
-                }
-            }))
-            .catch((e) =>
+        this.enqueue(
+            req.socket,
+            () => this.readNodes(opts)
+                .then(graphData => ({
+                    channel: req.channel,
+                    data   : {
+                        '#'  : msgId,
+                        'put': graphData
+                    }
+                }))
+                .catch((e) =>
+                {
+                    // tslint:disable-next-line: no-console
+                    console.warn(e.stack || e);
+                    return {
+                        channel: req.channel,
+                        data   : {
+                            '#'  : msgId,
+                            '@'  : req['#'],
+                            'err': 'Error fetching node'
+                        }
+                    }
+                })
+                .then((msg) =>
+                {
+                    req.socket.transmit('#publish', msg);
+                }),
+            () =>
             {
-                // tslint:disable-next-line: no-console
-                console.warn(e.stack || e);
-                return {
+                req.socket.transmit('#publish', {
                     channel: req.channel,
                     data   : {
                         '#'  : msgId,
                         '@'  : req['#'],
-                        'err': 'Error fetching node'
+                        'err': 'Too many pending messages'
                     }
-                }
-            })
-            .then((msg) =>
-            {
-                req.socket.transmit('#publish', msg);
-            })
+                });
+            },
+        );
+    }
+
+    /**
+     * Only node subscriptions count towards the limit, ack channels of puts are never blocked
+     */
+    private assertSubscriptionLimit(socket: TGSocket, channel: string): void
+    {
+        const maxSubscriptions = this.options.maxSubscriptionsPerSocket;
+
+        if (!isNumber(maxSubscriptions))
+        {
+            return;
+        }
+
+        // The channel being subscribed to isn't in the socket subscriptions yet
+        const nodeChannels: string[] = socket.subscriptions().filter(nodeChannelSoul);
+
+        if (nodeChannels.indexOf(channel) === -1 && nodeChannels.length >= maxSubscriptions)
+        {
+            const error = new Error(`Subscription limit of ${maxSubscriptions} per socket reached`);
+            error.name  = 'SubscriptionLimitError';
+            throw error;
+        }
     }
 
     private readNodes(opts: TGOptionsGet): Promise<TGGraphData>
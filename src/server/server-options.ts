@@ -5,6 +5,10 @@ export interface TGServerOptions extends TGSocketServerOptions, TGGraphAdapterOp
 {
     disableValidation?: boolean;
     authMaxDrift?: number;
+    /** Maximum number of pending (queued or in-flight) puts and reads from a single socket */
+    maxQueuedMessages?: number;
+    /** Maximum number of node channels a single socket can be subscribed to at once */
+    maxSubscriptionsPerSocket?: number;
     ownerPub?: string;
     adapter?: TGGraphAdapter;
     port?: number;
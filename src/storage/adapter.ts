@@ -2,7 +2,7 @@ import { isNumber, isString } from 'topgun-typed';
 import { StorageListOptions, TGStorage } from './types';
 import { TGGraphAdapter, TGGraphAdapterOptions, TGGraphData, TGOptionsGet } from '../types';
 import { diffCRDT, mergeGraph } from '../crdt';
-import { assertPutEntry, storageListOptionsFromGetOptions } from './utils';
+import { assertPutEntry, getMany, storageListOptionsFromGetOptions } from './utils';
 
 const DEFAULT_CRDT_OPTS = {
     diffFn : diffCRDT,
@@ -13,7 +13,7 @@ export function createGraphAdapter(storage: TGStorage, adapterOptions?: TGGraphA
 {
     return {
         get: (opts: TGOptionsGet) => get(storage, opts),
-        put: (graphData: TGGraphData) => put(storage, graphData, adapterOptions),
+        put: (graphData: TGGraphData) => patchGraphFull(storage, graphData, adapterOptions),
     };
 }
 
@@ -58,40 +58,6 @@ async function getList(db: TGStorage, options: StorageListOptions): Promise<TGGr
     return await db.list(options);
 }
 
-async function put(
-    db: TGStorage,
-    data: TGGraphData,
-    adapterOptions?: TGGraphAdapterOptions,
-    crdtOptions = DEFAULT_CRDT_OPTS,
-): Promise<TGGraphData|null>
-{
-    const diff: TGGraphData = {};
-
-    for (const soul in data)
-    {
-        if (!soul)
-        {
-            continue;
-        }
-
-        const nodeDiff = await patchGraphFull(
-            db,
-            {
-                [soul]: data[soul],
-            },
-            adapterOptions,
-            crdtOptions,
-        );
-
-        if (nodeDiff)
-        {
-            diff[soul] = nodeDiff[soul];
-        }
-    }
-
-    return Object.keys(diff).length ? diff : null;
-}
-
 async function patchGraphFull(
     db: TGStorage,
     data: TGGraphData,
@@ -149,7 +115,7 @@ async function getPatchDiff(
         existingFromDiff[soul] = existing[soul];
     }
 
-    const updatedGraph = mergeFn(existing, graphDiff, 'mutable');
+    const updatedGraph = mergeFn(existingFromDiff, graphDiff, 'mutable');
 
     return {
         diff    : graphDiff,
@@ -164,16 +130,13 @@ async function getExisting(
 ): Promise<TGGraphData>
 {
     const existingData: TGGraphData = {};
+    const souls                     = Object.keys(data).filter(soul => !!soul);
+    const nodes                     = await getMany(db, souls);
 
-    for (const soul in data)
+    souls.forEach((soul, index) =>
     {
-        if (!soul)
-        {
-            continue;
-        }
-
-        existingData[soul] = await db.get(soul);
-    }
+        existingData[soul] = nodes[index];
+    });
 
     return existingData;
 }
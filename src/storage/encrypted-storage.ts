@@ -0,0 +1,138 @@
+import { isObject } from 'topgun-typed';
+import { TGGraphData, TGNode } from '../types';
+import { StorageListOptions, TGStorage } from './types';
+import { containsKeys, getMany } from './utils';
+import { check, decrypt, encrypt } from '../sea';
+import { Pair } from '../sea/pair';
+
+/**
+ * Field of the stored envelope node that holds the ciphertext
+ */
+export const ENCRYPTED_NODE_FIELD = 'sea';
+
+interface EncryptedPayload
+{
+    soul: string;
+    node: TGNode;
+}
+
+export interface EncryptedStorageOptions
+{
+    /** Return nodes that were stored before encryption was enabled as-is */
+    readPlaintext?: boolean;
+}
+
+/**
+ * Storage wrapper that encrypts nodes before they reach the underlying storage
+ * and decrypts them on read. Souls stay in plaintext so listing keeps working.
+ */
+export class EncryptedStorage implements TGStorage
+{
+    /**
+     * Constructor
+     */
+    constructor(
+        protected readonly storage: TGStorage,
+        protected readonly keyOrPair: string|Pair,
+        protected readonly options: EncryptedStorageOptions = {},
+    )
+    {
+    }
+
+    // -----------------------------------------------------------------------------------------------------
+    // @ Public methods
+    // -----------------------------------------------------------------------------------------------------
+
+    async put(key: string, value: TGNode): Promise<void>
+    {
+        // The soul is encrypted with the node so an envelope copied under another soul is rejected
+        const payload: EncryptedPayload = { soul: key, node: value };
+        const ciphertext                = await encrypt(JSON.stringify(payload), this.keyOrPair);
+
+        await this.storage.put(key, {
+            _                     : {
+                '#': key,
+                '>': {},
+            },
+            [ENCRYPTED_NODE_FIELD]: ciphertext,
+        });
+    }
+
+    async get(key: string): Promise<TGNode|null>
+    {
+        const stored = await this.storage.get(key);
+
+        return stored ? await this.decryptNode(key, stored) : null;
+    }
+
+    async getMany(keys: string[]): Promise<(TGNode|null)[]>
+    {
+        const stored = await getMany(this.storage, keys);
+
+        return Promise.all(stored.map((node, index) => node ? this.decryptNode(keys[index], node) : null));
+    }
+
+    containsKeys(keys: string[]): Promise<boolean[]>
+    {
+        // Envelopes are stored under the plaintext soul, no need to decrypt them
+        return containsKeys(this.storage, keys);
+    }
+
+    async list(options: StorageListOptions): Promise<TGGraphData>
+    {
+        const stored              = await this.storage.list(options);
+        const souls               = Object.keys(stored).filter(soul => !!soul);
+        const nodes               = await Promise.all(
+            souls.map(soul => stored[soul] ? this.decryptNode(soul, stored[soul] as TGNode) : null),
+        );
+        const result: TGGraphData = {};
+
+        souls.forEach((soul, index) =>
+        {
+            result[soul] = nodes[index];
+        });
+
+        return result;
+    }
+
+    // -----------------------------------------------------------------------------------------------------
+    // @ Protected methods
+    // -----------------------------------------------------------------------------------------------------
+
+    protected async decryptNode(key: string, stored: TGNode): Promise<TGNode>
+    {
+        const ciphertext = stored[ENCRYPTED_NODE_FIELD];
+
+        if (!this.isEnvelope(stored))
+        {
+            if (this.options.readPlaintext)
+            {
+                return stored;
+            }
+            throw new Error(`Node ${key} is not encrypted`);
+        }
+
+        const payload = await decrypt<EncryptedPayload>(ciphertext, this.keyOrPair);
+
+        if (!payload || !isObject(payload.node))
+        {
+            throw new Error(`Could not decrypt node ${key}`);
+        }
+        if (payload.soul !== key)
+        {
+            throw new Error(`Encrypted node ${payload.soul} is stored under ${key}`);
+        }
+
+        return payload.node;
+    }
+
+    /**
+     * Data fields always have a state entry, the envelope is written without one
+     */
+    protected isEnvelope(stored: TGNode): boolean
+    {
+        const state = (stored._ && stored._['>']) || {};
+
+        return state[ENCRYPTED_NODE_FIELD] === undefined && check(stored[ENCRYPTED_NODE_FIELD]);
+    }
+}
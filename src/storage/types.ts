@@ -6,6 +6,12 @@ export interface TGStorage
 
     get(key: string): Promise<TGNode|null>;
 
+    /** Batch read, results are aligned to the order of the given keys */
+    getMany?(keys: string[]): Promise<(TGNode|null)[]>;
+
+    /** Batch existence check, results are aligned to the order of the given keys */
+    containsKeys?(keys: string[]): Promise<boolean[]>;
+
     list(options: StorageListOptions): Promise<TGGraphData>
 }
 
@@ -2,7 +2,7 @@ import { isNumber, isString, isDefined } from 'topgun-typed';
 import textEncoder from 'topgun-textencoder';
 import { MAX_KEY_SIZE, MAX_VALUE_SIZE } from './constants';
 import { LEX, TGGraphAdapterOptions, TGGraphData, TGNode, TGOptionsGet } from '../types';
-import { StorageListOptions } from './types';
+import { StorageListOptions, TGStorage } from './types';
 import { getNodeSoul } from '../utils/node';
 import { assertNotEmptyString } from '../utils/assert';
 
@@ -173,4 +173,26 @@ export function listFilterMatch(
         (options?.start !== undefined && lexicographicCompare(name, options.start) < 0) ||
         (options?.end !== undefined && lexicographicCompare(name, options.end) >= 0)
     );
+}
+
+export async function getMany(storage: TGStorage, keys: string[]): Promise<(TGNode|null)[]>
+{
+    if (storage.getMany)
+    {
+        return storage.getMany(keys);
+    }
+
+    return Promise.all(keys.map(key => storage.get(key)));
+}
+
+export async function containsKeys(storage: TGStorage, keys: string[]): Promise<boolean[]>
+{
+    if (storage.containsKeys)
+    {
+        return storage.containsKeys(keys);
+    }
+
+    const nodes = await getMany(storage, keys);
+
+    return nodes.map(node => !!node);
 }
\ No newline at end of file
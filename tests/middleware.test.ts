@@ -0,0 +1,229 @@
+import { Middleware } from '../src/server/middleware';
+import { createGraphAdapter } from '../src/storage';
+import { MemoryStorage } from '../src/memory-adapter';
+import { TGGraphAdapter, TGGraphData } from '../src/types';
+
+interface Deferred
+{
+    promise: Promise<void>;
+    resolve: () => void;
+}
+
+function defer(): Deferred
+{
+    let resolve: () => void = () => undefined;
+    const promise           = new Promise<void>(r => resolve = r);
+
+    return { promise, resolve };
+}
+
+function createFakeServer(): { server: any, middleware: { [type: string]: (req: any) => void } }
+{
+    const middleware: { [type: string]: (req: any) => void } = {};
+    const server                                              = {
+        MIDDLEWARE_SUBSCRIBE : 'subscribe',
+        MIDDLEWARE_PUBLISH_IN: 'publishIn',
+        addMiddleware        : (type: string, fn: (req: any) => void) =>
+        {
+            middleware[type] = fn;
+        },
+    };
+
+    return { server, middleware };
+}
+
+function createFakeSocket(): {
+    socket: any,
+    sent: any[],
+    channels: string[],
+    waitForSent: (count: number) => Promise<void>
+}
+{
+    const channels: string[]                                = [];
+    const sent: any[]                                       = [];
+    const waiters: { count: number, resolve: () => void }[] = [];
+    const socket                                            = {
+        subscriptions: () => channels,
+        transmit     : (_event: string, msg: any) =>
+        {
+            sent.push(msg.data);
+            waiters
+                .filter(waiter => sent.length >= waiter.count)
+                .forEach(waiter => waiter.resolve());
+        },
+    };
+    const waitForSent                                       = (count: number) => new Promise<void>((resolve) =>
+    {
+        if (sent.length >= count)
+        {
+            return resolve();
+        }
+        waiters.push({ count, resolve });
+    });
+
+    return { socket, sent, channels, waitForSent };
+}
+
+function putRequest(socket: any, id: string, soul: string): any
+{
+    return {
+        socket,
+        channel: 'topgun/put',
+        data   : {
+            '#'  : id,
+            'put': { [soul]: { _: { '#': soul, '>': { name: 1 } }, name: soul } },
+        },
+    };
+}
+
+describe('Middleware', () =>
+{
+    let applied: string[];
+    let delays: { [soul: string]: Deferred };
+    let adapter: TGGraphAdapter;
+
+    beforeEach(() =>
+    {
+        const inner = createGraphAdapter(new MemoryStorage());
+
+        applied = [];
+        delays  = {};
+        adapter = {
+            get: opts => inner.get(opts),
+            put: async (graph: TGGraphData) =>
+            {
+                const soul = Object.keys(graph)[0];
+                if (delays[soul])
+                {
+                    await delays[soul].promise;
+                }
+                applied.push(soul);
+                return inner.put(graph);
+            },
+        };
+    });
+
+    it('applies puts from one socket in send order', async () =>
+    {
+        const { server, middleware }        = createFakeServer();
+        const { socket, sent, waitForSent } = createFakeSocket();
+        new Middleware(server, {}, adapter).setupMiddleware();
+        delays.slow = defer();
+
+        middleware.publishIn(putRequest(socket, '1', 'slow'));
+        middleware.publishIn(putRequest(socket, '2', 'fast'));
+        delays.slow.resolve();
+        await waitForSent(2);
+
+        expect(applied).toEqual(['slow', 'fast']);
+        expect(sent.map(ack => ack['@'])).toEqual(['1', '2']);
+    });
+
+    it('reads after a pending put from the same socket', async () =>
+    {
+        const { server, middleware }        = createFakeServer();
+        const { socket, sent, waitForSent } = createFakeSocket();
+        new Middleware(server, {}, adapter).setupMiddleware();
+        delays.user = defer();
+
+        middleware.publishIn(putRequest(socket, '1', 'user'));
+        middleware.subscribe({ socket, channel: 'topgun/nodes/user', data: { '#': 'user' } });
+        delays.user.resolve();
+        await waitForSent(2);
+
+        expect(sent[1].put).toEqual({
+            user: { _: { '#': 'user', '>': { name: 1 } }, name: 'user' },
+        });
+    });
+
+    it('interleaves puts from different sockets', async () =>
+    {
+        const { server, middleware } = createFakeServer();
+        const first                  = createFakeSocket();
+        const second                 = createFakeSocket();
+        new Middleware(server, {}, adapter).setupMiddleware();
+        delays.slow = defer();
+
+        middleware.publishIn(putRequest(first.socket, '1', 'slow'));
+        middleware.publishIn(putRequest(second.socket, '2', 'fast'));
+        await second.waitForSent(1);
+
+        expect(applied).toEqual(['fast']);
+
+        delays.slow.resolve();
+        await first.waitForSent(1);
+
+        expect(applied).toEqual(['fast', 'slow']);
+    });
+
+    it('rejects messages over the per-socket pending limit in send order', async () =>
+    {
+        const { server, middleware }        = createFakeServer();
+        const { socket, sent, waitForSent } = createFakeSocket();
+        new Middleware(server, { maxQueuedMessages: 2 }, adapter).setupMiddleware();
+        delays.slow = defer();
+
+        middleware.publishIn(putRequest(socket, '1', 'slow'));
+        middleware.subscribe({ socket, channel: 'topgun/nodes/slow', data: { '#': 'slow' } });
+        middleware.publishIn(putRequest(socket, '2', 'fast'));
+        middleware.subscribe({ socket, channel: 'topgun/nodes/fast', data: { '#': 'fast' } });
+
+        expect(sent).toEqual([]);
+
+        delays.slow.resolve();
+        await waitForSent(4);
+
+        expect(applied).toEqual(['slow']);
+        expect(sent).toEqual([
+            expect.objectContaining({ '@': '1', 'ok': true }),
+            expect.objectContaining({ 'put': { slow: expect.objectContaining({ name: 'slow' }) } }),
+            expect.objectContaining({ '@': '2', 'ok': false, 'err': 'Too many pending messages' }),
+            expect.objectContaining({ 'err': 'Too many pending messages' }),
+        ]);
+    });
+
+    it('limits the number of subscriptions per socket', async () =>
+    {
+        const { server, middleware }                  = createFakeServer();
+        const { socket, channels, sent, waitForSent } = createFakeSocket();
+        new Middleware(server, { maxSubscriptionsPerSocket: 2 }, adapter).setupMiddleware();
+
+        const subscribe = async (soul: string) =>
+        {
+            const channel = `topgun/nodes/${soul}`;
+            await middleware.subscribe({ socket, channel, data: { '#': soul } });
+            channels.push(channel);
+        };
+
+        await subscribe('first');
+        await subscribe('second');
+        await waitForSent(2);
+
+        await expect(subscribe('third')).rejects.toMatchObject({
+            name   : 'SubscriptionLimitError',
+            message: 'Subscription limit of 2 per socket reached',
+        });
+        expect(channels).toEqual(['topgun/nodes/first', 'topgun/nodes/second']);
+
+        channels.splice(channels.indexOf('topgun/nodes/first'), 1);
+        await subscribe('third');
+        await waitForSent(3);
+
+        expect(channels).toEqual(['topgun/nodes/second', 'topgun/nodes/third']);
+        expect(sent).toHaveLength(3);
+    });
+
+    it('does not count ack channels or repeated subscriptions towards the limit', async () =>
+    {
+        const { server, middleware } = createFakeServer();
+        const { socket, channels }   = createFakeSocket();
+        new Middleware(server, { maxSubscriptionsPerSocket: 1 }, adapter).setupMiddleware();
+        channels.push('topgun/@ack', 'topgun/nodes/changelog', 'topgun/nodes/first');
+
+        await expect(middleware.subscribe({ socket, channel: 'topgun/@put', data: {} })).resolves.toBeUndefined();
+        await expect(middleware.subscribe({ socket, channel: 'topgun/nodes/first', data: { '#': 'first' } }))
+            .resolves.toBeUndefined();
+        await expect(middleware.subscribe({ socket, channel: 'topgun/nodes/second', data: { '#': 'second' } }))
+            .rejects.toMatchObject({ name: 'SubscriptionLimitError' });
+    });
+});
@@ -0,0 +1,239 @@
+import * as SEA from '../src/sea';
+import {
+    containsKeys,
+    createGraphAdapter,
+    EncryptedStorage,
+    ENCRYPTED_NODE_FIELD,
+    getMany,
+    TGStorage,
+} from '../src/storage';
+import { IndexedDBStorage } from '../src/indexeddb/indexeddb-storage';
+import { MemoryStorage } from '../src/memory-adapter';
+import { TGNode } from '../src/types';
+import * as Server from '../src/server';
+
+const node: TGNode = {
+    _   : {
+        '#': 'user/alice',
+        '>': { name: 1, age: 1 },
+    },
+    name: 'Alice',
+    age : 30,
+};
+
+describe('EncryptedStorage', () =>
+{
+    it('round-trip returns the original node', async () =>
+    {
+        const pair    = await SEA.pair();
+        const storage = new EncryptedStorage(new MemoryStorage(), pair.epriv);
+
+        await storage.put('user/alice', node);
+
+        expect(await storage.get('user/alice')).toEqual(node);
+        expect(await storage.list({ prefix: 'user/' })).toEqual({ 'user/alice': node });
+    });
+
+    it('stored data differs from plaintext', async () =>
+    {
+        const pair    = await SEA.pair();
+        const memory  = new MemoryStorage();
+        const storage = new EncryptedStorage(memory, pair.epriv);
+
+        await storage.put('user/alice', node);
+
+        const stored = memory.getSync('user/alice') as TGNode;
+        expect(stored.name).toBeUndefined();
+        expect(stored[ENCRYPTED_NODE_FIELD]).not.toContain('Alice');
+    });
+
+    it('wrong key fails decryption', async () =>
+    {
+        const pair      = await SEA.pair();
+        const otherPair = await SEA.pair();
+        const memory    = new MemoryStorage();
+
+        await new EncryptedStorage(memory, pair.epriv).put('user/alice', node);
+
+        await expect(new EncryptedStorage(memory, otherPair.epriv).get('user/alice'))
+            .rejects.toThrow('Could not decrypt node user/alice');
+    });
+
+    it('rejects an envelope copied under another soul', async () =>
+    {
+        const pair    = await SEA.pair();
+        const memory  = new MemoryStorage();
+        const storage = new EncryptedStorage(memory, pair.epriv);
+
+        await storage.put('user/alice', node);
+        memory.putSync('user/bob', {
+            ...(memory.getSync('user/alice') as TGNode),
+            _: { '#': 'user/bob', '>': {} },
+        });
+
+        await expect(storage.get('user/bob'))
+            .rejects.toThrow('Encrypted node user/alice is stored under user/bob');
+        await expect(storage.list({ prefix: 'user/' })).rejects.toThrow();
+    });
+
+    it('reads plaintext nodes when migrating', async () =>
+    {
+        const pair   = await SEA.pair();
+        const memory = new MemoryStorage();
+        memory.putSync('user/alice', node);
+
+        await expect(new EncryptedStorage(memory, pair.epriv).get('user/alice')).rejects.toThrow();
+
+        const storage = new EncryptedStorage(memory, pair.epriv, { readPlaintext: true });
+        expect(await storage.get('user/alice')).toEqual(node);
+
+        await storage.put('user/alice', node);
+        expect((memory.getSync('user/alice') as TGNode).name).toBeUndefined();
+        expect(await storage.get('user/alice')).toEqual(node);
+    });
+
+    it('reads plaintext nodes with a sea field when migrating', async () =>
+    {
+        const pair           = await SEA.pair();
+        const memory         = new MemoryStorage();
+        const legacy: TGNode = {
+            _  : {
+                '#': 'user/bob',
+                '>': { sea: 1 },
+            },
+            sea: 'SEA{"ct":"not encrypted"}',
+        };
+        memory.putSync('user/bob', legacy);
+
+        const storage = new EncryptedStorage(memory, pair.epriv, { readPlaintext: true });
+        expect(await storage.get('user/bob')).toEqual(legacy);
+    });
+
+    it('is exported from the server entry', async () =>
+    {
+        const pair    = await SEA.pair();
+        const adapter = Server.createGraphAdapter(
+            new Server.EncryptedStorage(new Server.MemoryStorage(), pair.epriv),
+        );
+
+        await adapter.put({ 'user/alice': node });
+        expect(await adapter.get({ '#': 'user/alice' })).toEqual({ 'user/alice': node });
+    });
+});
+
+function createFakeIndexedDB(data: Map<string, TGNode>, modes: string[]): any
+{
+    const store = {
+        get  : (key: string) => ({ result: data.get(key) }),
+        count: (key: string) => ({ result: data.has(key) ? 1 : 0 }),
+    };
+    const db    = {
+        createObjectStore: () => store,
+        transaction      : (_name: string, mode: string) =>
+        {
+            const transaction: any  = {};
+            transaction.objectStore = () => store;
+            modes.push(mode);
+            setTimeout(() => transaction.oncomplete());
+            return transaction;
+        },
+    };
+
+    return {
+        open: () =>
+        {
+            const req: any = { result: db };
+            setTimeout(() => req.onsuccess());
+            return req;
+        },
+    };
+}
+
+describe('Storage batch reads', () =>
+{
+    it('containsKeys is aligned to input order', async () =>
+    {
+        const memory = new MemoryStorage();
+        memory.putSync('a', { _: { '#': 'a', '>': {} } });
+        memory.putSync('c', { _: { '#': 'c', '>': {} } });
+        const getManySpy = jest.spyOn(memory, 'getMany');
+
+        expect(await containsKeys(memory, ['c', 'b', 'a', 'd'])).toEqual([true, false, true, false]);
+        expect(getManySpy).not.toHaveBeenCalled();
+    });
+
+    it('containsKeys falls back to getMany', async () =>
+    {
+        const pair    = await SEA.pair();
+        const memory  = new MemoryStorage();
+        const storage = new EncryptedStorage({
+            get    : key => memory.get(key),
+            getMany: keys => memory.getMany(keys),
+            put    : (key, value) => memory.put(key, value),
+            list   : options => memory.list(options),
+        }, pair.epriv);
+        await storage.put('user/alice', node);
+
+        expect(await containsKeys(storage, ['user/bob', 'user/alice'])).toEqual([false, true]);
+    });
+
+    it('getMany returns null for missing keys', async () =>
+    {
+        const pair    = await SEA.pair();
+        const storage = new EncryptedStorage(new MemoryStorage(), pair.epriv);
+        await storage.put('user/alice', node);
+
+        expect(await getMany(storage, ['user/bob', 'user/alice'])).toEqual([null, node]);
+    });
+
+    it('getMany falls back to get for storages without batch reads', async () =>
+    {
+        const memory             = new MemoryStorage();
+        const storage: TGStorage = {
+            get : key => memory.get(key),
+            put : (key, value) => memory.put(key, value),
+            list: options => memory.list(options),
+        };
+        memory.putSync('user/alice', node);
+
+        expect(await getMany(storage, ['user/alice', 'user/bob'])).toEqual([node, null]);
+    });
+
+    it('adapter reads existing nodes for all souls of a put in one batch', async () =>
+    {
+        const memory     = new MemoryStorage();
+        const getManySpy = jest.spyOn(memory, 'getMany');
+        const getSpy     = jest.spyOn(memory, 'get');
+        const adapter    = createGraphAdapter(memory);
+
+        await adapter.put({
+            'user/alice': node,
+            'user/bob'  : { _: { '#': 'user/bob', '>': { name: 1 } }, name: 'Bob' },
+        });
+
+        expect(getManySpy).toHaveBeenCalledTimes(1);
+        expect(getManySpy).toHaveBeenCalledWith(['user/alice', 'user/bob']);
+        expect(getSpy).not.toHaveBeenCalled();
+        expect(memory.getSync('user/bob')).toEqual(expect.objectContaining({ name: 'Bob' }));
+    });
+
+    it('IndexedDBStorage batch reads use one readonly transaction each', async () =>
+    {
+        const modes: string[] = [];
+        const data            = new Map<string, TGNode>([['user/alice', node]]);
+        (global as any).window = { indexedDB: createFakeIndexedDB(data, modes) };
+
+        try
+        {
+            const storage = new IndexedDBStorage('test');
+
+            expect(await storage.getMany(['user/bob', 'user/alice'])).toEqual([null, node]);
+            expect(await containsKeys(storage, ['user/alice', 'user/bob'])).toEqual([true, false]);
+            expect(modes).toEqual(['readonly', 'readonly']);
+        }
+        finally
+        {
+            delete (global as any).window;
+        }
+    });
+});
@@ -0,0 +1,57 @@
+import { TGClient } from '../src/client';
+import { TGServer } from '../src/server';
+import { TGMessage } from '../src/types';
+
+const PORT_NUMBER = 3458;
+let server: TGServer, client: TGClient;
+
+describe('Subscription limit', () =>
+{
+    beforeEach(async () =>
+    {
+        client = new TGClient({
+            peers: [{
+                hostname: '127.0.0.1',
+                port    : PORT_NUMBER,
+            }]
+        });
+        server = new TGServer({
+            port                     : PORT_NUMBER,
+            maxSubscriptionsPerSocket: 2,
+        });
+    });
+    afterEach(async () =>
+    {
+        await Promise.all([
+            client.disconnect(),
+            server.close()
+        ]);
+    });
+
+    it('should ack puts when node subscriptions are at the limit', async () =>
+    {
+        await Promise.all([
+            server.waitForReady(),
+            client.waitForConnect()
+        ]);
+
+        const put  = (key: string, value: string) => new Promise<TGMessage>((resolve) =>
+        {
+            client.get(key).put({ value }, ack => resolve(ack));
+        });
+        const read = (key: string) => new Promise<any>((resolve) =>
+        {
+            client.get(key).on(data => resolve(data));
+        });
+
+        await put('first', 'a');
+        await put('second', 'b');
+        expect(await read('first')).toEqual(expect.objectContaining({ value: 'a' }));
+        expect(await read('second')).toEqual(expect.objectContaining({ value: 'b' }));
+
+        const ack = await put('first', 'c');
+
+        expect(ack.err).toBeFalsy();
+        expect(ack.ok).toBeTruthy();
+    });
+});